@@ -7,22 +7,37 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::cmp::Ordering;
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 use crate::cache::dircache::DirCache;
 use crate::top_items::common::{dir_exists, format_table};
 
 use humansize::{file_size_opts, FileSize};
 use rayon::iter::*;
+use regex::Regex;
+use serde::Serialize;
 use walkdir::WalkDir;
 
+/// How the summary of a `git_checkouts_stats()` call should be rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// The default, human-readable `format_table` output.
+    Human,
+    /// One JSON record per crate, sizes in raw bytes.
+    Json,
+    /// One CSV record per crate, sizes in raw bytes.
+    Csv,
+}
+
 #[derive(Clone, Debug)]
 struct FileDesc {
     path: PathBuf,
     name: String,
     size: u64,
+    /// Most recent modification time seen while walking this checkout.
+    mtime: SystemTime,
 }
 
 #[inline]
@@ -52,88 +67,71 @@ impl FileDesc {
 
         let walkdir = WalkDir::new(path.display().to_string());
 
-        let size = walkdir
+        let metadatas: Vec<fs::Metadata> = walkdir
             .into_iter()
             .map(|e| e.unwrap().path().to_owned())
             .filter(|f| f.exists())
             .collect::<Vec<_>>()
             .par_iter()
             .map(|f| {
-                fs::metadata(f)
-                    .unwrap_or_else(|_| {
-                        panic!("Failed to get metadata of file '{}'", &path.display())
-                    })
-                    .len()
+                fs::metadata(f).unwrap_or_else(|_| {
+                    panic!("Failed to get metadata of file '{}'", &path.display())
+                })
             })
-            .sum();
+            .collect();
+
+        let size = metadatas.iter().map(fs::Metadata::len).sum();
+        let mtime = metadatas
+            .iter()
+            .filter_map(|m| m.modified().ok())
+            .max()
+            .unwrap_or(SystemTime::UNIX_EPOCH);
 
         Self {
             name,
             size,
+            mtime,
             path: path.into(),
         }
     } // fn new_from_git_checkouts()
 } // impl FileDesc
 
-#[derive(Clone, Debug, Eq)]
+#[derive(Clone, Debug)]
 pub(crate) struct ChkInfo {
     name: String,
-    size: u64,
     counter: u32,
-    total_size: u64, // sorted by this
+    total_size: u64,
+    /// Most recent modification time seen across all of this crate's checkouts.
+    mtime: SystemTime,
 }
 
 impl ChkInfo {
-    // sorted by total_size!
-
-    fn new(path: &PathBuf, counter: u32, total_size: u64) -> Self {
-        let name: String;
-        let size: u64;
-        if path.exists() {
-            let mut a = path.clone();
-            a.pop();
-            let name_tmp = a.file_name().unwrap().to_str().unwrap().to_string();
-            size = fs::metadata(&path)
-                .unwrap_or_else(|_| panic!("Failed to get metadata of file '{}'", &path.display()))
-                .len();
-            let mut tmp = name_tmp.split('-').collect::<Vec<_>>();
-            let _ = tmp.pop();
-            name = tmp.join("-");
-        } else {
-            let name_tmp = path
-                .file_name()
-                .unwrap()
-                .to_os_string()
-                .into_string()
-                .unwrap();
-            size = 0;
-            name = name_tmp;
-        }
+    fn new(name: String, counter: u32, total_size: u64, mtime: SystemTime) -> Self {
         Self {
             name,
-            size,
             counter,
             total_size,
+            mtime,
         }
     }
-}
 
-impl PartialOrd for ChkInfo {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for ChkInfo {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.total_size.cmp(&other.total_size)
+    /// The value to compare by when sorting a list of `ChkInfo`, chosen at call time
+    /// via `sort_by` rather than being hardcoded into an `Ord` impl.
+    fn sort_key(&self, sort_by: SortBy) -> u64 {
+        match sort_by {
+            SortBy::Total => self.total_size,
+            SortBy::Average => self.total_size / u64::from(self.counter),
+            SortBy::Count => u64::from(self.counter),
+        }
     }
 }
 
-impl PartialEq for ChkInfo {
-    fn eq(&self, other: &Self) -> bool {
-        self.total_size == other.total_size
-    }
+/// Which field of a [`ChkInfo`] row to sort the checkouts summary by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SortBy {
+    Total,
+    Average,
+    Count,
 }
 
 #[inline]
@@ -142,129 +140,199 @@ fn file_desc_from_path(cache: &mut DirCache) -> Vec<FileDesc> {
     cache
         .git_checkouts
         .checkout_folders()
-        .iter()
-        .map(|path| FileDesc::new_from_git_checkouts(path))
+        .par_iter()
+        .map(FileDesc::new_from_git_checkouts)
         .collect::<Vec<_>>()
 }
 
 #[inline]
 fn stats_from_file_desc_list(file_descs: Vec<FileDesc>) -> Vec<ChkInfo> {
-    struct Pair {
-        current: Option<FileDesc>,
-        previous: Option<FileDesc>,
-    }
-    // take our list of file information and calculate the actual stats
-    let mut out: Vec<ChkInfo> = Vec::new();
-    let mut chkinfo: ChkInfo = ChkInfo::new(&PathBuf::from("ERROR 1/err1"), 0, 0);
-    let mut counter: u32 = 0; // how many of a crate do we have
-    let mut total_size: u64 = 0; // total size of these crates
-
-    // iterate over the fikles
-    let mut iter = file_descs.into_iter();
-
-    let mut state = Pair {
-        current: None,
-        previous: None,
-    };
+    use std::collections::HashMap;
+
+    // group checkouts by crate name instead of relying on adjacent FileDescs sharing a name
+    // (file_desc_from_path() does not guarantee that checkouts of the same crate are contiguous)
+    let mut grouped: HashMap<String, (u32, u64, SystemTime)> = HashMap::new();
+    for file_desc in file_descs {
+        let entry = grouped
+            .entry(file_desc.name)
+            .or_insert((0, 0, SystemTime::UNIX_EPOCH));
+        entry.0 += 1;
+        entry.1 += file_desc.size;
+        entry.2 = entry.2.max(file_desc.mtime);
+    }
 
-    // start looping
-    state.previous = state.current;
-    state.current = iter.next();
-
-    // loop until .previous and .current are None which means we are at the end
-    while state.previous.is_some() || state.current.is_some() {
-        match &state {
-            Pair {
-                current: None,
-                previous: None,
-            } => {
-                // we reached the end of the queue
-                unreachable!("dead code triggered: while loop condition did not hold inside match");
-            }
+    grouped
+        .into_iter()
+        .map(|(name, (counter, total_size, mtime))| ChkInfo::new(name, counter, total_size, mtime))
+        .collect()
+}
 
-            Pair {
-                current: Some(current),
-                previous: None,
-            } => {
-                // this should always be first line ever
-                // @TODO assert that  chkinfo is empty
-                // compute line but don't save it
-                let current_size = &current.size;
-                total_size += current_size;
-                counter += 1;
-
-                chkinfo = ChkInfo::new(&current.path, counter, total_size);
-            }
+/// One machine-readable record of the summary, sizes in raw bytes (no humansize lossiness).
+#[derive(Clone, Debug, Serialize)]
+struct ChkInfoRecord {
+    name: String,
+    count: u32,
+    average_size: u64,
+    total_size: u64,
+}
 
-            Pair {
-                current: Some(current),
-                previous: Some(previous),
-            } => {
-                if current.name == previous.name {
-                    // update line but don't save it
-                    // @TODO assert that chkinfo is not empty
-                    let current_size = &current.size;
-                    total_size += current_size;
-                    counter += 1;
-
-                    chkinfo = ChkInfo::new(&current.path, counter, total_size);
-                } else if current.name != previous.name {
-                    // save old line
-                    // @TODO assert that dbg_line is not empty
-                    out.push(chkinfo);
-                    // reset counters
-                    counter = 0;
-                    total_size = 0;
-                    // and update line
-                    let current_size = &current.size;
-                    total_size += current_size;
-                    counter += 1;
-
-                    chkinfo = ChkInfo::new(&current.path, counter, total_size);
-                }
-            }
+impl From<&ChkInfo> for ChkInfoRecord {
+    fn from(chkout: &ChkInfo) -> Self {
+        Self {
+            name: chkout.name.clone(),
+            count: chkout.counter,
+            average_size: chkout.total_size / u64::from(chkout.counter),
+            total_size: chkout.total_size,
+        }
+    }
+}
 
-            Pair {
-                current: None,
-                previous: Some(_previous),
-            } => {
-                // save old line
-                // @TODO assert that ChkInfo is not empty
-                out.push(chkinfo);
-                chkinfo = ChkInfo::new(&PathBuf::from("ERROR 2/err2"), 0, 0); // uninit
-
-                // reset counters
-                counter = 0;
-                total_size = 0;
-            }
-        };
+fn chkout_list_to_json(collections_vec: &[ChkInfo]) -> String {
+    let records: Vec<ChkInfoRecord> = collections_vec.iter().map(ChkInfoRecord::from).collect();
+    serde_json::to_string_pretty(&records).unwrap_or_default()
+}
 
-        // switch and queue next()
-        state.previous = state.current;
-        state.current = iter.next();
+fn chkout_list_to_csv(collections_vec: &[ChkInfo]) -> String {
+    let mut out = String::from("name,count,average_size,total_size\n");
+    for record in collections_vec.iter().map(ChkInfoRecord::from) {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            record.name, record.count, record.average_size, record.total_size
+        ));
     }
     out
 }
 
+/// Detected width of the controlling terminal, falling back to a fixed 80 columns
+/// when stdout is not a TTY (e.g. piped into a file or another program).
+fn terminal_width() -> usize {
+    term_size::dimensions_stdout()
+        .map(|(width, _height)| width)
+        .unwrap_or(80)
+}
+
+/// Width (in characters) the "Name", "Count", "Average" and "Total" columns plus their
+/// separating padding are expected to take up, so the usage bar gets whatever is left.
+const NON_BAR_COLUMNS_WIDTH: usize = 45;
+/// Extra width (in characters) the "Last used" column plus its separating padding takes up,
+/// added on top of `NON_BAR_COLUMNS_WIDTH` when `show_last_used` is set.
+const LAST_USED_COLUMN_WIDTH: usize = 14;
+const MIN_BAR_WIDTH: usize = 10;
+
+/// Renders a dutree-style proportional bar, e.g. `████░░░░`, scaled so that
+/// `max_total_size` fills `width` and everything else is scaled relative to it.
+fn usage_bar(total_size: u64, max_total_size: u64, width: usize) -> String {
+    if width == 0 || max_total_size == 0 {
+        return String::new();
+    }
+    let filled = (width as f64 * total_size as f64 / max_total_size as f64).round() as usize;
+    let filled = filled.min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// Renders how long ago a checkout was last touched, for the "Last used" column.
+fn format_age(mtime: SystemTime) -> String {
+    match SystemTime::now().duration_since(mtime) {
+        Ok(elapsed) => format!("{}d ago", elapsed.as_secs() / (24 * 60 * 60)),
+        Err(_) => String::from("just now"),
+    }
+}
+
+/// Parses durations like `30d` or `6w` into a `Duration`, for `--older-than`.
+fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    // this parses untrusted CLI text, so it must fail gracefully rather than panic; bail out
+    // on anything non-ASCII instead of slicing on a byte offset that might not be a char
+    // boundary (e.g. a single multi-byte unit character)
+    if !input.is_ascii() {
+        return None;
+    }
+    let split_at = input.len().checked_sub(1)?;
+    let (number, unit) = input.split_at(split_at);
+    let number: u64 = number.parse().ok()?;
+    let days = match unit {
+        "d" => number,
+        "w" => number.checked_mul(7)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(days.checked_mul(24 * 60 * 60)?))
+}
+
 #[inline] // only use din one place
-fn chkout_list_to_string(limit: u32, mut collections_vec: Vec<ChkInfo>) -> String {
+fn chkout_list_to_string(
+    limit: u32,
+    format: OutputFormat,
+    show_usage_bar: bool,
+    show_last_used: bool,
+    sort_by: SortBy,
+    name_filter: Option<&Regex>,
+    older_than: Option<Duration>,
+    mut collections_vec: Vec<ChkInfo>,
+) -> String {
+    if let Some(name_filter) = name_filter {
+        collections_vec.retain(|chkout| name_filter.is_match(&chkout.name));
+    }
+
+    if let Some(older_than) = older_than {
+        // checked_sub() only fails if older_than reaches back further than SystemTime can
+        // represent; nothing can be older than that, so no row passes the filter
+        match SystemTime::now().checked_sub(older_than) {
+            Some(cutoff) => collections_vec.retain(|chkout| chkout.mtime <= cutoff),
+            None => collections_vec.clear(),
+        }
+    }
+
     if collections_vec.is_empty() {
         return String::new();
     }
 
     // sort the ChkInfo Vec in reverse
-    collections_vec.sort();
+    collections_vec.sort_by_key(|chkout| chkout.sort_key(sort_by));
     collections_vec.reverse();
+    collections_vec.truncate(limit as usize);
+
+    match format {
+        OutputFormat::Json => return chkout_list_to_json(&collections_vec),
+        OutputFormat::Csv => return chkout_list_to_csv(&collections_vec),
+        OutputFormat::Human => {}
+    }
+
+    // the largest checkout (by total size) fills the whole bar, regardless of sort_by
+    let max_total_size = collections_vec
+        .iter()
+        .map(|chkout| chkout.total_size)
+        .max()
+        .unwrap_or(0);
+    let bar_width = if show_usage_bar {
+        let non_bar_columns_width = if show_last_used {
+            NON_BAR_COLUMNS_WIDTH + LAST_USED_COLUMN_WIDTH
+        } else {
+            NON_BAR_COLUMNS_WIDTH
+        };
+        terminal_width()
+            .saturating_sub(non_bar_columns_width)
+            .max(MIN_BAR_WIDTH)
+    } else {
+        0
+    };
+
     let mut table_matrix: Vec<Vec<String>> = Vec::new();
 
-    table_matrix.push(vec![
+    let mut header = vec![
         String::from("Name"),
         String::from("Count"),
         String::from("Average"),
         String::from("Total"),
-    ]);
+    ];
+    if show_last_used {
+        header.push(String::from("Last used"));
+    }
+    if show_usage_bar {
+        header.push(String::from("Usage"));
+    }
+    table_matrix.push(header);
 
-    for chkout in collections_vec.into_iter().take(limit as usize) {
+    for chkout in collections_vec {
         let average_size = (chkout.total_size / u64::from(chkout.counter))
             .file_size(file_size_opts::DECIMAL)
             .unwrap();
@@ -273,19 +341,31 @@ fn chkout_list_to_string(limit: u32, mut collections_vec: Vec<ChkInfo>) -> Strin
             .file_size(file_size_opts::DECIMAL)
             .unwrap();
 
-        table_matrix.push(vec![
-            chkout.name,
-            chkout.counter.to_string(),
-            average_size,
-            total_size,
-        ]);
+        let mut row = vec![chkout.name, chkout.counter.to_string(), average_size, total_size];
+        if show_last_used {
+            row.push(format_age(chkout.mtime));
+        }
+        if show_usage_bar {
+            row.push(usage_bar(chkout.total_size, max_total_size, bar_width));
+        }
+        table_matrix.push(row);
     }
 
     format_table(&table_matrix)
 }
 
 #[inline]
-pub(crate) fn git_checkouts_stats(path: &PathBuf, limit: u32, mut cache: &mut DirCache) -> String {
+pub(crate) fn git_checkouts_stats(
+    path: &PathBuf,
+    limit: u32,
+    format: OutputFormat,
+    show_usage_bar: bool,
+    show_last_used: bool,
+    sort_by: SortBy,
+    name_filter: Option<&Regex>,
+    older_than: Option<Duration>,
+    mut cache: &mut DirCache,
+) -> String {
     let mut output = String::new();
     // don't crash if the directory does not exist (issue #9)
     if !dir_exists(path) {
@@ -305,17 +385,226 @@ pub(crate) fn git_checkouts_stats(path: &PathBuf, limit: u32, mut cache: &mut Di
     let collections_vec = file_desc_from_path(&mut cache);
     let summary: Vec<ChkInfo> = stats_from_file_desc_list(collections_vec);
 
-    let tmp = chkout_list_to_string(limit, summary);
+    let tmp = chkout_list_to_string(
+        limit,
+        format,
+        show_usage_bar,
+        show_last_used,
+        sort_by,
+        name_filter,
+        older_than,
+        summary,
+    );
     output.push_str(&tmp);
 
     output
 }
 
+/// Reclaimable duplication found across a crate's checked-out revisions.
+#[derive(Clone, Debug)]
+pub(crate) struct DupInfo {
+    name: String,
+    /// Total bytes that are byte-for-byte identical across 2+ revisions.
+    duplicate_bytes: u64,
+    /// Bytes that could be freed by keeping one copy of each byte-identical file instead of
+    /// the duplicates found across revisions (i.e. `duplicate_bytes` minus one surviving copy
+    /// per duplicated file, not the size of any whole revision).
+    reclaimable_bytes: u64,
+}
+
+/// Content hash used to confirm that same-sized files are actually identical, not just
+/// coincidentally the same size. Not cryptographic; speed over files is what matters here.
+fn content_hash(path: &PathBuf) -> u64 {
+    use std::hash::Hasher;
+    use twox_hash::XxHash64;
+
+    let bytes = fs::read(path).unwrap_or_default();
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write(&bytes);
+    hasher.finish()
+}
+
+/// `(relative path within the checkout, absolute path, size)` for every plain file in one
+/// git checkout revision. Symlinks are skipped; `WalkDir` does not follow them by default.
+fn files_in_checkout_revision(revision: &PathBuf) -> Vec<(PathBuf, PathBuf, u64)> {
+    WalkDir::new(revision)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.path().to_owned())
+        .filter(|f| f.exists()) // files can disappear mid-scan
+        .filter_map(|path| {
+            let relative = path.strip_prefix(revision).ok()?.to_owned();
+            let size = fs::metadata(&path).ok()?.len();
+            Some((relative, path, size))
+        })
+        .collect()
+}
+
+/// Finds duplicated file content across all checkout revisions of a single crate.
+fn dup_info_for_crate(name: String, revisions: &[PathBuf]) -> DupInfo {
+    use std::collections::HashMap;
+
+    // bucket by (relative path, size) first, so files with a unique size never get hashed
+    let mut by_path_and_size: HashMap<(PathBuf, u64), Vec<PathBuf>> = HashMap::new();
+    for revision in revisions {
+        for (relative, absolute, size) in files_in_checkout_revision(revision) {
+            by_path_and_size
+                .entry((relative, size))
+                .or_default()
+                .push(absolute);
+        }
+    }
+
+    let mut duplicate_bytes: u64 = 0;
+    let mut reclaimable_bytes: u64 = 0;
+
+    for ((_relative, size), paths) in &by_path_and_size {
+        if paths.len() < 2 {
+            continue; // present in only one revision, nothing to reclaim
+        }
+
+        // group by actual content hash: a stray revision with a differing file (e.g. an
+        // updated Cargo.lock) must not hide the duplication between the other revisions
+        let mut by_hash: HashMap<u64, u64> = HashMap::new();
+        for path in paths {
+            *by_hash.entry(content_hash(path)).or_insert(0) += 1;
+        }
+
+        for count in by_hash.values() {
+            if *count >= 2 {
+                duplicate_bytes += size * count;
+                reclaimable_bytes += size * (count - 1);
+            }
+        }
+    }
+
+    DupInfo {
+        name,
+        duplicate_bytes,
+        reclaimable_bytes,
+    }
+}
+
+#[inline]
+fn duplicate_checkouts_stats(cache: &mut DirCache) -> Vec<DupInfo> {
+    use std::collections::HashMap;
+
+    let mut by_crate: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for path in cache.git_checkouts.checkout_folders() {
+        by_crate
+            .entry(name_from_pb(path))
+            .or_default()
+            .push(path.clone());
+    }
+
+    by_crate
+        .into_iter()
+        .map(|(name, revisions)| dup_info_for_crate(name, &revisions))
+        .collect()
+}
+
+fn dup_list_to_string(limit: u32, mut dups: Vec<DupInfo>) -> String {
+    dups.retain(|dup| dup.duplicate_bytes > 0);
+    if dups.is_empty() {
+        return String::new();
+    }
+
+    dups.sort_by_key(|dup| dup.reclaimable_bytes);
+    dups.reverse();
+
+    let mut table_matrix: Vec<Vec<String>> = Vec::new();
+    table_matrix.push(vec![
+        String::from("Name"),
+        String::from("Duplicated"),
+        String::from("Could free"),
+    ]);
+
+    for dup in dups.into_iter().take(limit as usize) {
+        table_matrix.push(vec![
+            dup.name,
+            dup.duplicate_bytes
+                .file_size(file_size_opts::DECIMAL)
+                .unwrap(),
+            dup.reclaimable_bytes
+                .file_size(file_size_opts::DECIMAL)
+                .unwrap(),
+        ]);
+    }
+
+    format_table(&table_matrix)
+}
+
+/// Summary of duplicated file content across git checkout revisions, so users can see how
+/// much space they could reclaim by deduplicating byte-identical files that are repeated
+/// across revisions. Flows through the same table/summary rendering path as
+/// [`git_checkouts_stats`].
+#[inline]
+pub(crate) fn git_checkouts_duplicates_stats(
+    path: &PathBuf,
+    limit: u32,
+    mut cache: &mut DirCache,
+) -> String {
+    let mut output = String::new();
+    if !dir_exists(path) {
+        return output;
+    }
+
+    output.push_str(&format!("\nDuplication in: {}\n", path.display()));
+
+    let dups = duplicate_checkouts_stats(&mut cache);
+    output.push_str(&dup_list_to_string(limit, dups));
+
+    output
+}
+
 #[cfg(test)]
 mod top_crates_git_checkouts {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn usage_bar_full() {
+        assert_eq!(usage_bar(10, 10, 8), "████████");
+    }
+
+    #[test]
+    fn usage_bar_half() {
+        assert_eq!(usage_bar(5, 10, 8), "████░░░░");
+    }
+
+    #[test]
+    fn usage_bar_zero_max() {
+        assert_eq!(usage_bar(0, 0, 8), "");
+    }
+
+    #[test]
+    fn parse_duration_days() {
+        assert_eq!(parse_duration("30d"), Some(Duration::from_secs(30 * 24 * 60 * 60)));
+    }
+
+    #[test]
+    fn parse_duration_weeks() {
+        assert_eq!(parse_duration("6w"), Some(Duration::from_secs(6 * 7 * 24 * 60 * 60)));
+    }
+
+    #[test]
+    fn parse_duration_invalid_unit() {
+        assert_eq!(parse_duration("30y"), None);
+    }
+
+    #[test]
+    fn parse_duration_invalid_number() {
+        assert_eq!(parse_duration("d"), None);
+    }
+
+    #[test]
+    fn parse_duration_multi_byte_input_does_not_panic() {
+        // "µ" is 2 bytes but 1 char; slicing at len() - 1 would land mid-character
+        assert_eq!(parse_duration("µ"), None);
+        assert_eq!(parse_duration("30µ"), None);
+    }
+
     #[test]
     fn name_from_pb_cargo_cache() {
         let path = PathBuf::from(
@@ -333,12 +622,190 @@ mod top_crates_git_checkouts {
         assert_eq!(name, "alacritty");
     }
 
+    #[test]
+    fn content_hash_same_bytes_match() {
+        use std::io::Write;
+        let mut a = std::env::temp_dir();
+        a.push("cargo_cache_test_content_hash_a");
+        let mut b = std::env::temp_dir();
+        b.push("cargo_cache_test_content_hash_b");
+        fs::File::create(&a).unwrap().write_all(b"hello").unwrap();
+        fs::File::create(&b).unwrap().write_all(b"hello").unwrap();
+
+        assert_eq!(content_hash(&a), content_hash(&b));
+
+        let _ = fs::remove_file(&a);
+        let _ = fs::remove_file(&b);
+    }
+
+    #[test]
+    fn content_hash_different_bytes_differ() {
+        use std::io::Write;
+        let mut a = std::env::temp_dir();
+        a.push("cargo_cache_test_content_hash_c");
+        let mut b = std::env::temp_dir();
+        b.push("cargo_cache_test_content_hash_d");
+        fs::File::create(&a).unwrap().write_all(b"hello").unwrap();
+        fs::File::create(&b).unwrap().write_all(b"world").unwrap();
+
+        assert_ne!(content_hash(&a), content_hash(&b));
+
+        let _ = fs::remove_file(&a);
+        let _ = fs::remove_file(&b);
+    }
+
+    #[test]
+    fn dup_info_for_crate_detects_duplicate_file() {
+        use std::io::Write;
+        let mut rev_a = std::env::temp_dir();
+        rev_a.push("cargo_cache_test_dup_rev_a");
+        let mut rev_b = std::env::temp_dir();
+        rev_b.push("cargo_cache_test_dup_rev_b");
+        fs::create_dir_all(&rev_a).unwrap();
+        fs::create_dir_all(&rev_b).unwrap();
+
+        let mut file_a = rev_a.clone();
+        file_a.push("same.txt");
+        let mut file_b = rev_b.clone();
+        file_b.push("same.txt");
+        fs::File::create(&file_a)
+            .unwrap()
+            .write_all(b"duplicate content")
+            .unwrap();
+        fs::File::create(&file_b)
+            .unwrap()
+            .write_all(b"duplicate content")
+            .unwrap();
+
+        let dup = dup_info_for_crate("some-crate".to_string(), &[rev_a.clone(), rev_b.clone()]);
+        assert_eq!(dup.duplicate_bytes, "duplicate content".len() as u64 * 2);
+        assert_eq!(dup.reclaimable_bytes, "duplicate content".len() as u64);
+
+        let _ = fs::remove_dir_all(&rev_a);
+        let _ = fs::remove_dir_all(&rev_b);
+    }
+
+    #[test]
+    fn dup_info_for_crate_one_differing_revision_does_not_hide_others_duplication() {
+        use std::io::Write;
+        let mut rev_a = std::env::temp_dir();
+        rev_a.push("cargo_cache_test_dup_3rev_a");
+        let mut rev_b = std::env::temp_dir();
+        rev_b.push("cargo_cache_test_dup_3rev_b");
+        let mut rev_c = std::env::temp_dir();
+        rev_c.push("cargo_cache_test_dup_3rev_c");
+        fs::create_dir_all(&rev_a).unwrap();
+        fs::create_dir_all(&rev_b).unwrap();
+        fs::create_dir_all(&rev_c).unwrap();
+
+        // rev_a and rev_b are byte-identical; rev_c's copy differs (e.g. an updated Cargo.lock)
+        let mut file_a = rev_a.clone();
+        file_a.push("Cargo.lock");
+        let mut file_b = rev_b.clone();
+        file_b.push("Cargo.lock");
+        let mut file_c = rev_c.clone();
+        file_c.push("Cargo.lock");
+        fs::File::create(&file_a)
+            .unwrap()
+            .write_all(b"duplicate content")
+            .unwrap();
+        fs::File::create(&file_b)
+            .unwrap()
+            .write_all(b"duplicate content")
+            .unwrap();
+        fs::File::create(&file_c)
+            .unwrap()
+            .write_all(b"different content")
+            .unwrap();
+
+        let dup = dup_info_for_crate(
+            "some-crate".to_string(),
+            &[rev_a.clone(), rev_b.clone(), rev_c.clone()],
+        );
+        // rev_a/rev_b's duplication must still be reported even though rev_c disagrees
+        assert_eq!(dup.duplicate_bytes, "duplicate content".len() as u64 * 2);
+        assert_eq!(dup.reclaimable_bytes, "duplicate content".len() as u64);
+
+        let _ = fs::remove_dir_all(&rev_a);
+        let _ = fs::remove_dir_all(&rev_b);
+        let _ = fs::remove_dir_all(&rev_c);
+    }
+
+    #[test]
+    fn chkout_list_to_string_json_format() {
+        let fd1 = FileDesc {
+            path: PathBuf::from("crateA"),
+            name: "crateA".to_string(),
+            size: 10,
+            mtime: SystemTime::UNIX_EPOCH,
+        };
+        let fd2 = FileDesc {
+            path: PathBuf::from("crateA"),
+            name: "crateA".to_string(),
+            size: 7,
+            mtime: SystemTime::UNIX_EPOCH,
+        };
+        let list_fd: Vec<FileDesc> = vec![fd1, fd2];
+        let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
+        let is: String = chkout_list_to_string(
+            10,
+            OutputFormat::Json,
+            false,
+            false,
+            SortBy::Total,
+            None,
+            None,
+            list_cb,
+        );
+
+        let parsed: serde_json::Value = serde_json::from_str(&is).unwrap();
+        let records = parsed.as_array().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["name"], "crateA");
+        assert_eq!(records[0]["count"], 2);
+        // raw bytes, not a humansize string, and integer-divided: (10 + 7) / 2 == 8
+        assert_eq!(records[0]["average_size"], 8);
+        assert_eq!(records[0]["total_size"], 17);
+    }
+
+    #[test]
+    fn chkout_list_to_string_csv_format() {
+        let fd1 = FileDesc {
+            path: PathBuf::from("crateA"),
+            name: "crateA".to_string(),
+            size: 10,
+            mtime: SystemTime::UNIX_EPOCH,
+        };
+        let fd2 = FileDesc {
+            path: PathBuf::from("crateA"),
+            name: "crateA".to_string(),
+            size: 7,
+            mtime: SystemTime::UNIX_EPOCH,
+        };
+        let list_fd: Vec<FileDesc> = vec![fd1, fd2];
+        let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
+        let is: String = chkout_list_to_string(
+            10,
+            OutputFormat::Csv,
+            false,
+            false,
+            SortBy::Total,
+            None,
+            None,
+            list_cb,
+        );
+
+        // raw bytes, not humansize strings; average_size is integer-divided: (10 + 7) / 2 == 8
+        let wanted = String::from("name,count,average_size,total_size\ncrateA,2,8,17\n");
+        assert_eq!(is, wanted);
+    }
+
     #[test]
     fn stats_from_file_desc_none() {
         // empty list
         let list: Vec<FileDesc> = Vec::new();
         let stats = stats_from_file_desc_list(list);
-        let is = chkout_list_to_string(4, stats);
+        let is = chkout_list_to_string(4, OutputFormat::Human, false, false, SortBy::Total, None, None, stats);
         let empty = String::new();
         assert_eq!(is, empty);
     }
@@ -349,10 +816,11 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crateA"),
             name: "crateA".to_string(),
             size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let list_fd: Vec<FileDesc> = vec![fd];
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-        let is: String = chkout_list_to_string(1, list_cb);
+        let is: String = chkout_list_to_string(1, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
         let wanted = String::from("Name   Count Average Total\ncrateA 1     1 B     1 B\n");
         assert_eq!(is, wanted);
     }
@@ -363,15 +831,17 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-B"),
             name: "crate-B".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let list_fd: Vec<FileDesc> = vec![fd1, fd2];
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-        let is: String = chkout_list_to_string(3, list_cb);
+        let is: String = chkout_list_to_string(3, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
 
         let mut wanted = String::new();
         for i in &[
@@ -390,31 +860,36 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-B"),
             name: "crate-B".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd3 = FileDesc {
             path: PathBuf::from("crate-C"),
             name: "crate-C".to_string(),
             size: 10,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd4 = FileDesc {
             path: PathBuf::from("crate-D"),
             name: "crate-D".to_string(),
             size: 6,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd5 = FileDesc {
             path: PathBuf::from("crate-E"),
             name: "crate-E".to_string(),
             size: 4,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3, fd4, fd5];
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
 
-        let is: String = chkout_list_to_string(6, list_cb);
+        let is: String = chkout_list_to_string(6, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
 
         let mut wanted = String::new();
         for i in &[
@@ -436,16 +911,18 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 3,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 3,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let list_fd: Vec<FileDesc> = vec![fd1, fd2];
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-        let is: String = chkout_list_to_string(2, list_cb);
+        let is: String = chkout_list_to_string(2, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
         let wanted = String::from("Name    Count Average Total\ncrate-A 2     3 B     6 B\n");
         assert_eq!(is, wanted);
     }
@@ -456,22 +933,25 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 3,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 3,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd3 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 3,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3];
 
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-        let is: String = chkout_list_to_string(3, list_cb);
+        let is: String = chkout_list_to_string(3, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
         let wanted = String::from("Name    Count Average Total\ncrate-A 3     3 B     9 B\n");
         assert_eq!(is, wanted);
     }
@@ -482,21 +962,24 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 4,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd3 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 12,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3];
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-        let is: String = chkout_list_to_string(3, list_cb);
+        let is: String = chkout_list_to_string(3, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
         let wanted = String::from("Name    Count Average Total\ncrate-A 3     6 B     18 B\n");
         assert_eq!(is, wanted);
     }
@@ -507,49 +990,57 @@ mod top_crates_git_checkouts {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 4,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd3 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 12,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let fd4 = FileDesc {
             path: PathBuf::from("crate-B"),
             name: "crate-B".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd5 = FileDesc {
             path: PathBuf::from("crate-B"),
             name: "crate-B".to_string(),
             size: 8,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let fd6 = FileDesc {
             path: PathBuf::from("crate-C"),
             name: "crate-C".to_string(),
             size: 0,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd7 = FileDesc {
             path: PathBuf::from("crate-C"),
             name: "crate-C".to_string(),
             size: 100,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let fd8 = FileDesc {
             path: PathBuf::from("crate-D"),
             name: "crate-D".to_string(),
             size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3, fd4, fd5, fd6, fd7, fd8];
         let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-        let is: String = chkout_list_to_string(5, list_cb);
+        let is: String = chkout_list_to_string(5, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
 
         let mut wanted = String::new();
 
@@ -565,6 +1056,184 @@ mod top_crates_git_checkouts {
         assert_eq!(is, wanted);
     }
 
+    #[test]
+    fn chkout_list_to_string_name_filter() {
+        let fd1 = FileDesc {
+            path: PathBuf::from("serde"),
+            name: "serde".to_string(),
+            size: 10,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+        let fd2 = FileDesc {
+            path: PathBuf::from("serde_json"),
+            name: "serde_json".to_string(),
+            size: 4,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+        let fd3 = FileDesc {
+            path: PathBuf::from("alacritty"),
+            name: "alacritty".to_string(),
+            size: 100,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+
+        let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3];
+        let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
+        let filter = Regex::new("^serde").unwrap();
+        let is: String =
+            chkout_list_to_string(10, OutputFormat::Human, false, false, SortBy::Total, Some(&filter), None, list_cb);
+
+        let mut wanted = String::new();
+        for i in &[
+            "Name       Count Average Total\n",
+            "serde      1     10 B    10 B\n",
+            "serde_json 1     4 B     4 B\n",
+        ] {
+            wanted.push_str(i);
+        }
+        assert_eq!(is, wanted);
+    }
+
+    #[test]
+    fn chkout_list_to_string_sort_by_count() {
+        let fd1 = FileDesc {
+            path: PathBuf::from("crate-A"),
+            name: "crate-A".to_string(),
+            size: 100,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+        let fd2 = FileDesc {
+            path: PathBuf::from("crate-B"),
+            name: "crate-B".to_string(),
+            size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+        let fd3 = FileDesc {
+            path: PathBuf::from("crate-B"),
+            name: "crate-B".to_string(),
+            size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+
+        let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3];
+        let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
+        let is: String =
+            chkout_list_to_string(10, OutputFormat::Human, false, false, SortBy::Count, None, None, list_cb);
+
+        let mut wanted = String::new();
+        for i in &[
+            "Name    Count Average Total\n",
+            "crate-B 2     1 B     2 B\n",
+            "crate-A 1     100 B   100 B\n",
+        ] {
+            wanted.push_str(i);
+        }
+        assert_eq!(is, wanted);
+    }
+
+    #[test]
+    fn chkout_list_to_string_sort_by_average() {
+        // crate-A has the bigger total (120) but the smaller average (60), crate-B the
+        // opposite (100 total, 100 average) - Average must disagree with both Total and Count
+        let fd1 = FileDesc {
+            path: PathBuf::from("crate-A"),
+            name: "crate-A".to_string(),
+            size: 60,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+        let fd2 = FileDesc {
+            path: PathBuf::from("crate-A"),
+            name: "crate-A".to_string(),
+            size: 60,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+        let fd3 = FileDesc {
+            path: PathBuf::from("crate-B"),
+            name: "crate-B".to_string(),
+            size: 100,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
+        };
+
+        let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3];
+        let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
+        let is: String = chkout_list_to_string(
+            10,
+            OutputFormat::Human,
+            false,
+            false,
+            SortBy::Average,
+            None,
+            None,
+            list_cb,
+        );
+
+        let mut wanted = String::new();
+        for i in &[
+            "Name    Count Average Total\n",
+            "crate-B 1     100 B   100 B\n",
+            "crate-A 2     60 B    120 B\n",
+        ] {
+            wanted.push_str(i);
+        }
+        assert_eq!(is, wanted);
+    }
+
+    #[test]
+    fn chkout_list_to_string_usage_bar_and_last_used_together() {
+        // terminal_width() falls back to 80 columns when stdout isn't a TTY, as in `cargo test`
+        let bar_width = 80 - (NON_BAR_COLUMNS_WIDTH + LAST_USED_COLUMN_WIDTH);
+
+        let now = SystemTime::now();
+        let list_cb = vec![
+            ChkInfo::new("crate-A".to_string(), 1, 100, now),
+            ChkInfo::new("crate-B".to_string(), 1, 50, now),
+        ];
+        let is: String = chkout_list_to_string(
+            10,
+            OutputFormat::Human,
+            true,
+            true,
+            SortBy::Total,
+            None,
+            None,
+            list_cb,
+        );
+
+        let bar_a = usage_bar(100, 100, bar_width);
+        let bar_b = usage_bar(50, 100, bar_width);
+
+        let mut wanted = String::new();
+        wanted.push_str("Name    Count Average Total Last used Usage\n");
+        wanted.push_str(&format!("crate-A 1     100 B   100 B 0d ago    {}\n", bar_a));
+        wanted.push_str(&format!("crate-B 1     50 B    50 B  0d ago    {}\n", bar_b));
+        assert_eq!(is, wanted);
+    }
+
+    #[test]
+    fn chkout_list_to_string_older_than_filters_recent() {
+        let old = ChkInfo::new(
+            "old-crate".to_string(),
+            1,
+            10,
+            SystemTime::now() - Duration::from_secs(40 * 24 * 60 * 60),
+        );
+        let new = ChkInfo::new("new-crate".to_string(), 1, 10, SystemTime::now());
+
+        let is = chkout_list_to_string(
+            10,
+            OutputFormat::Human,
+            false,
+            false,
+            SortBy::Total,
+            None,
+            Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            vec![old, new],
+        );
+
+        assert!(is.contains("old-crate"));
+        assert!(!is.contains("new-crate"));
+    }
+
 }
 
 #[cfg(all(test, feature = "bench"))]
@@ -579,44 +1248,52 @@ mod benchmarks {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd2 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 4,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd3 = FileDesc {
             path: PathBuf::from("crate-A"),
             name: "crate-A".to_string(),
             size: 12,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let fd4 = FileDesc {
             path: PathBuf::from("crate-B"),
             name: "crate-B".to_string(),
             size: 2,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd5 = FileDesc {
             path: PathBuf::from("crate-B"),
             name: "crate-B".to_string(),
             size: 8,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let fd6 = FileDesc {
             path: PathBuf::from("crate-C"),
             name: "crate-C".to_string(),
             size: 0,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
         let fd7 = FileDesc {
             path: PathBuf::from("crate-C"),
             name: "crate-C".to_string(),
             size: 100,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let fd8 = FileDesc {
             path: PathBuf::from("crate-D"),
             name: "crate-D".to_string(),
             size: 1,
+            mtime: std::time::SystemTime::UNIX_EPOCH,
         };
 
         let list_fd: Vec<FileDesc> = vec![fd1, fd2, fd3, fd4, fd5, fd6, fd7, fd8];
@@ -624,7 +1301,7 @@ mod benchmarks {
         b.iter(|| {
             let list_fd = list_fd.clone(); // @FIXME  don't?
             let list_cb: Vec<ChkInfo> = stats_from_file_desc_list(list_fd);
-            let is: String = chkout_list_to_string(5, list_cb);
+            let is: String = chkout_list_to_string(5, OutputFormat::Human, false, false, SortBy::Total, None, None, list_cb);
 
             black_box(is);
         });